@@ -0,0 +1,12 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Core Synacor VM interpreter. Builds on `core` + `alloc` alone, so it can be
+//! embedded in a `no_std` host; the `std` feature (enabled by default) adds
+//! file loading and the stdio-backed `InputSource`/`OutputSink`.
+
+#[macro_use]
+extern crate log;
+extern crate alloc;
+
+pub mod memory;
+pub mod vm;
@@ -1,17 +1,17 @@
 #[macro_use]
 extern crate log;
 extern crate env_logger;
+extern crate synacor_challenge;
 
-mod memory;
-mod vm;
-
-use memory::Memory;
-use vm::VM;
+use synacor_challenge::memory::Memory;
+use synacor_challenge::vm::VM;
 
 fn main() {
     env_logger::init().unwrap();
 
     let mem = Memory::challenge_bin();
     let mut vm = VM::new(mem);
-    vm.run();
+    if let Err(trap) = vm.run() {
+        error!("{:#06x}: {}", vm.ip(), trap);
+    }
 }
@@ -1,6 +1,10 @@
+use core::ops::{AddAssign, Index, IndexMut, Deref};
+
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::ops::{AddAssign, Index, IndexMut, Deref};
+#[cfg(feature = "std")]
 use std::path::Path;
 
 pub const MEMORY_SIZE: usize = 1 << 15;
@@ -34,6 +38,7 @@ impl IndexMut<usize> for Memory {
     }
 }
 
+#[cfg(feature = "std")]
 impl Memory {
     pub fn load<R: Read>(reader: R) -> Memory {
         let mut mem = Memory::new();
@@ -62,10 +67,24 @@ impl Memory {
     pub fn challenge_bin() -> Memory {
         Memory::load_file(Path::new(env!("CARGO_MANIFEST_DIR")).join("challenge").join("challenge.bin"))
     }
+}
 
+impl Memory {
     pub fn pointer(&self, addr: usize) -> Pointer {
         Pointer::new(self, addr)
     }
+
+    /// Non-panicking counterpart to `Index`: returns `None` instead of
+    /// panicking when `addr` is out of bounds.
+    pub fn get(&self, addr: usize) -> Option<u16> {
+        self.0.get(addr).cloned()
+    }
+
+    /// Non-panicking counterpart to `IndexMut`: returns `None` instead of
+    /// panicking when `addr` is out of bounds.
+    pub fn get_mut(&mut self, addr: usize) -> Option<&mut u16> {
+        self.0.get_mut(addr)
+    }
 }
 
 pub struct Pointer<'a> {
@@ -81,6 +100,21 @@ impl<'a> Pointer<'a> {
     pub fn jump(&mut self, addr: usize) {
         self.addr = addr;
     }
+
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+
+    /// Non-panicking counterpart to the `Iterator`/`Deref`-based access:
+    /// returns `None` instead of panicking when the pointer has run past
+    /// the end of memory, and only advances past a word it actually read.
+    pub fn try_next(&mut self) -> Option<u16> {
+        let value = self.mem.get(self.addr);
+        if value.is_some() {
+            self.addr += 1;
+        }
+        value
+    }
 }
 
 impl<'a>AddAssign<usize> for Pointer<'a> {
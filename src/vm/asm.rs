@@ -0,0 +1,234 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+use super::super::memory::{Memory, MEMORY_SIZE};
+use super::NUM_REGISTERS;
+
+/// An error encountered while assembling a textual listing, together with
+/// the (1-based) source line it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssembleError {
+    UnknownMnemonic(usize, String),
+    WrongOperandCount(usize),
+    InvalidOperand(usize, String),
+    UnknownLabel(usize, String),
+    AddressOverflow(usize),
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AssembleError::UnknownMnemonic(line, ref mnemonic) => write!(f, "line {}: unknown mnemonic {:?}", line, mnemonic),
+            AssembleError::WrongOperandCount(line) => write!(f, "line {}: wrong number of operands", line),
+            AssembleError::InvalidOperand(line, ref operand) => write!(f, "line {}: invalid operand {:?}", line, operand),
+            AssembleError::UnknownLabel(line, ref label) => write!(f, "line {}: unknown label {:?}", line, label),
+            AssembleError::AddressOverflow(line) => write!(f, "line {}: listing runs past the end of memory", line),
+        }
+    }
+}
+
+/// Opcode and operand count for each of the 22 mnemonics, the inverse of the
+/// match in `Instruction::try_from_pointer`.
+fn opcode(mnemonic: &str) -> Option<(u16, usize)> {
+    match mnemonic {
+        "halt" => Some((0, 0)),
+        "set" => Some((1, 2)),
+        "push" => Some((2, 1)),
+        "pop" => Some((3, 1)),
+        "eq" => Some((4, 3)),
+        "gt" => Some((5, 3)),
+        "jmp" => Some((6, 1)),
+        "jt" => Some((7, 2)),
+        "jf" => Some((8, 2)),
+        "add" => Some((9, 3)),
+        "mult" => Some((10, 3)),
+        "mod" => Some((11, 3)),
+        "and" => Some((12, 3)),
+        "or" => Some((13, 3)),
+        "not" => Some((14, 2)),
+        "rmem" => Some((15, 2)),
+        "wmem" => Some((16, 2)),
+        "call" => Some((17, 1)),
+        "ret" => Some((18, 0)),
+        "out" => Some((19, 1)),
+        "in" => Some((20, 1)),
+        "noop" => Some((21, 0)),
+        _ => None,
+    }
+}
+
+enum Statement {
+    Instruction { line: usize, addr: usize, opcode: u16, operands: Vec<String> },
+    Data { addr: usize, word: u16 },
+}
+
+/// Pseudo-mnemonic `Disassembler` emits for a word that doesn't decode as an
+/// instruction, so a full dump (code and data alike) reassembles byte-for-byte.
+const DATA_DIRECTIVE: &str = ".data";
+
+/// Assembles a textual listing into a `Memory` image. This is the inverse of
+/// `Disassembler`: one mnemonic per (non-blank) line, operands are either
+/// numeric literals below `0x8000` or register names `R0`-`R7`, a line
+/// consisting of just `label:` defines a label that `Jmp`/`Jt`/`Jf`/`Call`
+/// operands can refer to by name, and `.data <word>` emits a raw `u16` word
+/// (matching `DecodedItem::Invalid`) without interpreting it as an operand.
+pub fn assemble(source: &str) -> Result<Memory, AssembleError> {
+    let mut labels = BTreeMap::new();
+    let mut statements = Vec::new();
+    let mut addr = 0;
+
+    for (i, raw_line) in source.lines().enumerate() {
+        let line = i + 1;
+        let text = match raw_line.find(';') {
+            Some(pos) => raw_line[..pos].trim(),
+            None => raw_line.trim(),
+        };
+        if text.is_empty() {
+            continue;
+        }
+
+        if let Some(label) = text.strip_suffix(':') {
+            labels.insert(label.trim().to_string(), addr);
+            continue;
+        }
+
+        let mut tokens = text.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty());
+        let mnemonic = tokens.next().unwrap_or("");
+
+        if mnemonic.eq_ignore_ascii_case(DATA_DIRECTIVE) {
+            let operand = tokens.next().ok_or(AssembleError::WrongOperandCount(line))?;
+            if tokens.next().is_some() {
+                return Err(AssembleError::WrongOperandCount(line));
+            }
+            let word = parse_literal(operand).ok_or_else(|| AssembleError::InvalidOperand(line, operand.to_string()))?;
+            if addr + 1 > MEMORY_SIZE {
+                return Err(AssembleError::AddressOverflow(line));
+            }
+            statements.push(Statement::Data { addr: addr, word: word });
+            addr += 1;
+            continue;
+        }
+
+        let (op, arity) = match opcode(&mnemonic.to_lowercase()) {
+            Some(info) => info,
+            None => return Err(AssembleError::UnknownMnemonic(line, mnemonic.to_string())),
+        };
+        let operands: Vec<String> = tokens.map(|t| t.to_string()).collect();
+        if operands.len() != arity {
+            return Err(AssembleError::WrongOperandCount(line));
+        }
+        if addr + 1 + arity > MEMORY_SIZE {
+            return Err(AssembleError::AddressOverflow(line));
+        }
+
+        statements.push(Statement::Instruction { line: line, addr: addr, opcode: op, operands: operands });
+        addr += 1 + arity;
+    }
+
+    let mut mem = Memory::new();
+    for statement in &statements {
+        match *statement {
+            Statement::Instruction { line, addr, opcode, ref operands } => {
+                mem[addr] = opcode;
+                for (i, operand) in operands.iter().enumerate() {
+                    let word = encode_operand(line, operand, &labels)?;
+                    mem[addr + 1 + i] = word;
+                }
+            },
+            Statement::Data { addr, word } => {
+                mem[addr] = word;
+            },
+        }
+    }
+    Ok(mem)
+}
+
+fn encode_operand(line: usize, operand: &str, labels: &BTreeMap<String, usize>) -> Result<u16, AssembleError> {
+    if let Some(register) = parse_register(operand) {
+        return Ok((MEMORY_SIZE + register) as u16);
+    }
+    if let Some(n) = parse_literal(operand) {
+        if (n as usize) >= MEMORY_SIZE {
+            return Err(AssembleError::InvalidOperand(line, operand.to_string()));
+        }
+        return Ok(n);
+    }
+    match labels.get(operand) {
+        Some(&addr) => Ok(addr as u16),
+        None => Err(AssembleError::UnknownLabel(line, operand.to_string())),
+    }
+}
+
+fn parse_register(operand: &str) -> Option<usize> {
+    let mut chars = operand.chars();
+    match (chars.next(), chars.as_str().parse::<usize>(), chars.as_str().len()) {
+        (Some('R'), Ok(r), 1) | (Some('r'), Ok(r), 1) if r < NUM_REGISTERS => Some(r),
+        _ => None,
+    }
+}
+
+fn parse_literal(operand: &str) -> Option<u16> {
+    if let Some(hex) = operand.strip_prefix("0x").or_else(|| operand.strip_prefix("0X")) {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        operand.parse::<u16>().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::disasm::Disassembler;
+    use super::fmt::Write;
+
+    #[test]
+    fn labels_and_registers() {
+        let mem = assemble("loop:\nset R0 4\njt R0 loop\nhalt\n").unwrap();
+        assert_eq!(mem[0], 1);
+        assert_eq!(mem[1], MEMORY_SIZE as u16);
+        assert_eq!(mem[2], 4);
+        assert_eq!(mem[3], 7);
+        assert_eq!(mem[4], MEMORY_SIZE as u16);
+        assert_eq!(mem[5], 0);
+        assert_eq!(mem[6], 0);
+    }
+
+    #[test]
+    fn data_directive() {
+        let mem = assemble(".data 0x8002\n").unwrap();
+        assert_eq!(mem[0], 0x8002);
+    }
+
+    #[test]
+    fn rejects_listing_that_overflows_memory() {
+        let mut source = String::new();
+        for _ in 0..MEMORY_SIZE {
+            source.push_str(".data 0\n");
+        }
+        source.push_str("halt\n");
+        assert_eq!(assemble(&source), Err(AssembleError::AddressOverflow(MEMORY_SIZE + 1)));
+    }
+
+    #[test]
+    fn round_trips_through_disassembler() {
+        let source = "loop:\nset R0 4\njt R0 loop\nhalt\n.data 0x002a\n";
+        let len = 3 + 3 + 1 + 1;
+        let original = assemble(source).unwrap();
+
+        let mut dump = String::new();
+        for item in Disassembler::new(&original, 0, len) {
+            writeln!(dump, "{}", item).unwrap();
+        }
+
+        let reassembled = assemble(&dump).unwrap();
+        for addr in 0..len {
+            assert_eq!(original[addr], reassembled[addr], "word at {:#06x} differs", addr);
+        }
+    }
+}
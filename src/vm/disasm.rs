@@ -0,0 +1,66 @@
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+use super::super::memory::{Memory, Pointer};
+use super::{Instruction, InvalidInstruction, TryFromPointer};
+
+/// One decoded unit from a linear sweep of memory: either a recognized
+/// instruction, or a word that doesn't decode to one and is treated as data.
+#[derive(Debug)]
+pub enum DecodedItem {
+    Instruction(usize, Instruction),
+    Invalid(usize, u16),
+}
+
+/// Renders in the textual form `assemble` accepts, so a disassembled dump
+/// can be fed straight back in: a bare mnemonic line for `Instruction`, or a
+/// `.data` directive for a word that doesn't decode as one.
+impl fmt::Display for DecodedItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodedItem::Instruction(_, ref ins) => write!(f, "{}", ins),
+            DecodedItem::Invalid(_, word) => write!(f, ".data {:#06x}", word),
+        }
+    }
+}
+
+/// Linearly sweeps `mem` from `start` up to (but not including) `limit`,
+/// decoding each word as an instruction. An unrecognized opcode, or operands
+/// that run past the end of memory, never abort the sweep: the word is
+/// yielded as `DecodedItem::Invalid` and the sweep advances by a single word.
+pub struct Disassembler<'a> {
+    mem: &'a Memory,
+    ptr: Pointer<'a>,
+    limit: usize,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(mem: &'a Memory, start: usize, limit: usize) -> Disassembler<'a> {
+        Disassembler { mem: mem, ptr: mem.pointer(start), limit: limit }
+    }
+}
+
+impl<'a> Iterator for Disassembler<'a> {
+    type Item = DecodedItem;
+
+    fn next(&mut self) -> Option<DecodedItem> {
+        if self.ptr.addr() >= self.limit {
+            return None;
+        }
+        let addr = self.ptr.addr();
+        match Instruction::try_from_pointer(&mut self.ptr) {
+            Ok(instruction) => Some(DecodedItem::Instruction(addr, instruction)),
+            Err(InvalidInstruction(_)) => {
+                // `try_from_pointer` may have failed on an operand rather than
+                // the opcode itself, in which case its error word is the
+                // faulting operand, not mem[addr]; re-read the word actually
+                // stored at `addr` so Invalid always reports what's there.
+                let word = self.mem.get(addr).unwrap_or(0);
+                self.ptr.jump(addr + 1);
+                Some(DecodedItem::Invalid(addr, word))
+            },
+        }
+    }
+}
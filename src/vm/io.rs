@@ -0,0 +1,34 @@
+/// A source of input bytes for the `In` instruction. `None` signals that the
+/// channel has closed (end of input).
+pub trait InputSource {
+    fn read_byte(&mut self) -> Option<u8>;
+}
+
+/// A sink for output bytes written by the `Out` instruction.
+pub trait OutputSink {
+    fn write_byte(&mut self, byte: u8);
+}
+
+/// Default `InputSource` that reads from the process' standard input.
+#[cfg(feature = "std")]
+pub struct StdinInput;
+
+#[cfg(feature = "std")]
+impl InputSource for StdinInput {
+    fn read_byte(&mut self) -> Option<u8> {
+        use std::io::Read;
+        std::io::stdin().bytes().next().and_then(|b| b.ok())
+    }
+}
+
+/// Default `OutputSink` that writes to the process' standard output.
+#[cfg(feature = "std")]
+pub struct StdoutOutput;
+
+#[cfg(feature = "std")]
+impl OutputSink for StdoutOutput {
+    fn write_byte(&mut self, byte: u8) {
+        use std::io::Write;
+        let _ = std::io::stdout().write_all(&[byte]);
+    }
+}
@@ -0,0 +1,695 @@
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::memory::{MEMORY_SIZE, Memory, Pointer};
+
+mod asm;
+mod disasm;
+mod io;
+
+pub use self::asm::{assemble, AssembleError};
+pub use self::disasm::{DecodedItem, Disassembler};
+pub use self::io::{InputSource, OutputSink};
+#[cfg(feature = "std")]
+pub use self::io::{StdinInput, StdoutOutput};
+
+/// Decodes a single item from the pointer, reporting an unrecognized word as
+/// `Err` instead of panicking.
+pub trait TryFromPointer: Sized {
+    fn try_from_pointer(ptr: &mut Pointer) -> Result<Self, InvalidInstruction>;
+}
+
+/// A word that couldn't be decoded as an instruction or operand, carried with
+/// the raw value that was read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidInstruction(pub u16);
+
+impl fmt::Display for InvalidInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid instruction {:#06x}", self.0)
+    }
+}
+
+/// An abnormal condition encountered while executing a program. Unlike the
+/// panics this replaces, a `Trap` is returned to the caller, which can inspect
+/// `VM::ip` for the address of the faulting instruction and decide how (or
+/// whether) to continue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    StackUnderflow,
+    MemoryOutOfBounds { addr: usize },
+    InvalidOperand(u16),
+    WriteToLiteral,
+    InvalidInstruction(u16),
+    InputClosed,
+    DivideByZero,
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Trap::StackUnderflow => write!(f, "Stack underflow"),
+            Trap::MemoryOutOfBounds { addr } => write!(f, "Memory access out of bounds ({:#06x})", addr),
+            Trap::InvalidOperand(n) => write!(f, "Invalid operand {:#06x}", n),
+            Trap::WriteToLiteral => write!(f, "Invalid write to literal operand"),
+            Trap::InvalidInstruction(n) => write!(f, "Invalid instruction {:#06x}", n),
+            Trap::InputClosed => write!(f, "Input channel closed"),
+            Trap::DivideByZero => write!(f, "Division by zero"),
+        }
+    }
+}
+
+
+#[derive(PartialEq, Eq)]
+pub enum Operand {
+    Literal(u16),
+    Register(u8),
+}
+
+impl Operand {
+    pub fn get(&self, vm: &VM) -> u16 {
+        match *self {
+            Operand::Literal(n) => n,
+            Operand::Register(r) => vm.reg[r as usize],
+        }
+    }
+
+    pub fn set(&self, vm: &mut VM, value: u16) -> Result<(), Trap> {
+        match *self {
+            Operand::Literal(_) => return Err(Trap::WriteToLiteral),
+            Operand::Register(r) => vm.reg[r as usize] = value,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operand::Literal(n) => f.write_fmt(format_args!("{:#x}", n)),
+            Operand::Register(r) => f.write_fmt(format_args!("R{:x}", r)),
+        }
+    }
+}
+
+impl From<u16> for Operand {
+    fn from(n: u16) -> Operand {
+        if (n as usize) < MEMORY_SIZE {
+            Operand::Literal(n)
+        } else if (n as usize) - MEMORY_SIZE < NUM_REGISTERS {
+            Operand::Register(((n as usize) - MEMORY_SIZE) as u8)
+        } else {
+            panic!("Invalid operand {:#06x}", n);
+        }
+    }
+}
+
+impl Operand {
+    fn try_from(n: u16) -> Result<Operand, InvalidInstruction> {
+        if (n as usize) < MEMORY_SIZE {
+            Ok(Operand::Literal(n))
+        } else if (n as usize) - MEMORY_SIZE < NUM_REGISTERS {
+            Ok(Operand::Register(((n as usize) - MEMORY_SIZE) as u8))
+        } else {
+            Err(InvalidInstruction(n))
+        }
+    }
+}
+
+impl TryFromPointer for Operand {
+    fn try_from_pointer(ptr: &mut Pointer) -> Result<Operand, InvalidInstruction> {
+        match ptr.try_next() {
+            Some(n) => Operand::try_from(n),
+            None => Err(InvalidInstruction(0)),
+        }
+    }
+}
+
+impl TryFromPointer for (Operand, Operand) {
+    fn try_from_pointer(ptr: &mut Pointer) -> Result<(Operand, Operand), InvalidInstruction> {
+        let a = Operand::try_from_pointer(ptr)?;
+        let b = Operand::try_from_pointer(ptr)?;
+        Ok((a, b))
+    }
+}
+
+impl TryFromPointer for (Operand, Operand, Operand) {
+    fn try_from_pointer(ptr: &mut Pointer) -> Result<(Operand, Operand, Operand), InvalidInstruction> {
+        let a = Operand::try_from_pointer(ptr)?;
+        let b = Operand::try_from_pointer(ptr)?;
+        let c = Operand::try_from_pointer(ptr)?;
+        Ok((a, b, c))
+    }
+}
+
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Halt,
+    Set(Operand, Operand),
+    Push(Operand),
+    Pop(Operand),
+    Eq(Operand, Operand, Operand),
+    Gt(Operand, Operand, Operand),
+    Jmp(Operand),
+    Jt(Operand, Operand),
+    Jf(Operand, Operand),
+    Add(Operand, Operand, Operand),
+    Mult(Operand, Operand, Operand),
+    Mod(Operand, Operand, Operand),
+    And(Operand, Operand, Operand),
+    Or(Operand, Operand, Operand),
+    Not(Operand, Operand),
+    RMem(Operand, Operand),
+    WMem(Operand, Operand),
+    Call(Operand),
+    Ret,
+    Out(Operand),
+    In(Operand),
+    Noop,
+}
+
+impl Instruction {
+    fn execute(&self, vm: &mut VM) -> Result<(), Trap> {
+        match *self {
+            Instruction::Halt => {
+                vm.halted = true
+            },
+            Instruction::Set(ref a, ref b) => {
+                let val = b.get(vm);
+                a.set(vm, val)?
+            },
+            Instruction::Push(ref a) => {
+                let val = a.get(vm);
+                vm.stack.push(val);
+            },
+            Instruction::Pop(ref a) => {
+                match vm.stack.pop() {
+                    Some(val) => a.set(vm, val)?,
+                    None => return Err(Trap::StackUnderflow),
+                }
+            },
+            Instruction::Eq(ref a, ref b, ref c) => {
+                match b.get(vm) == c.get(vm) {
+                    false => a.set(vm, 0)?,
+                    true => a.set(vm, 1)?,
+                }
+            },
+            Instruction::Gt(ref a, ref b, ref c) => {
+                match b.get(vm) > c.get(vm) {
+                    false => a.set(vm, 0)?,
+                    true => a.set(vm, 1)?,
+                }
+            },
+            Instruction::Jmp(ref a) => {
+                vm.ip = a.get(vm) as usize
+            },
+            Instruction::Jt(ref a, ref b) => {
+                if a.get(vm) != 0 {
+                    vm.ip = b.get(vm) as usize;
+                }
+            },
+            Instruction::Jf(ref a, ref b) => {
+                if a.get(vm) == 0 {
+                    vm.ip = b.get(vm) as usize;
+                }
+            },
+            Instruction::Add(ref a, ref b, ref c) => {
+                let val = (b.get(vm) + c.get(vm)) % 0x8000;
+                a.set(vm, val)?;
+            },
+            Instruction::Mult(ref a, ref b, ref c) => {
+                let val = ((b.get(vm) as u32 * c.get(vm) as u32) % 0x8000) as u16;
+                a.set(vm, val)?;
+            },
+            Instruction::Mod(ref a, ref b, ref c) => {
+                let divisor = c.get(vm);
+                if divisor == 0 {
+                    return Err(Trap::DivideByZero);
+                }
+                let val = b.get(vm) % divisor;
+                a.set(vm, val)?;
+            },
+            Instruction::And(ref a, ref b, ref c) => {
+                let val = b.get(vm) & c.get(vm);
+                a.set(vm, val)?;
+            },
+            Instruction::Or(ref a, ref b, ref c) => {
+                let val = b.get(vm) | c.get(vm);
+                a.set(vm, val)?;
+            },
+            Instruction::Not(ref a, ref b) => {
+                let val = !b.get(vm) & 0x7fff;
+                a.set(vm, val)?;
+            },
+            Instruction::RMem(ref a, ref b) => {
+                let addr = b.get(vm) as usize;
+                let val = match vm.mem.get(addr) {
+                    Some(val) => val,
+                    None => return Err(Trap::MemoryOutOfBounds { addr: addr }),
+                };
+                a.set(vm, val)?;
+            },
+            Instruction::WMem(ref a, ref b) => {
+                let addr = a.get(vm) as usize;
+                let val = b.get(vm);
+                match vm.mem.get_mut(addr) {
+                    Some(slot) => *slot = val,
+                    None => return Err(Trap::MemoryOutOfBounds { addr: addr }),
+                }
+            },
+            Instruction::Call(ref a) => {
+                vm.stack.push(vm.ip as u16);
+                vm.ip = a.get(vm) as usize;
+            },
+            Instruction::Ret => {
+                match vm.stack.pop() {
+                    Some(addr) => vm.ip = addr as usize,
+                    None => vm.halted = true,
+                }
+            },
+            Instruction::Out(ref ch) => {
+                let byte = ch.get(vm) as u8;
+                vm.output.write_byte(byte);
+            },
+            Instruction::In(ref a) => {
+                match vm.input.read_byte() {
+                    Some(byte) => a.set(vm, byte as u16)?,
+                    None => return Err(Trap::InputClosed),
+                }
+            },
+            Instruction::Noop => (),
+        }
+        Ok(())
+    }
+
+    /// The mnemonic `assemble` expects for this instruction, the inverse of
+    /// `opcode` in `asm.rs`.
+    fn mnemonic(&self) -> &'static str {
+        match *self {
+            Instruction::Halt => "halt",
+            Instruction::Set(..) => "set",
+            Instruction::Push(..) => "push",
+            Instruction::Pop(..) => "pop",
+            Instruction::Eq(..) => "eq",
+            Instruction::Gt(..) => "gt",
+            Instruction::Jmp(..) => "jmp",
+            Instruction::Jt(..) => "jt",
+            Instruction::Jf(..) => "jf",
+            Instruction::Add(..) => "add",
+            Instruction::Mult(..) => "mult",
+            Instruction::Mod(..) => "mod",
+            Instruction::And(..) => "and",
+            Instruction::Or(..) => "or",
+            Instruction::Not(..) => "not",
+            Instruction::RMem(..) => "rmem",
+            Instruction::WMem(..) => "wmem",
+            Instruction::Call(..) => "call",
+            Instruction::Ret => "ret",
+            Instruction::Out(..) => "out",
+            Instruction::In(..) => "in",
+            Instruction::Noop => "noop",
+        }
+    }
+}
+
+/// Renders an instruction as `assemble` expects it: mnemonic followed by
+/// space-separated operands, in `Operand`'s `Debug` form (`R0`, `0x4`, ...).
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())?;
+        match *self {
+            Instruction::Halt | Instruction::Ret | Instruction::Noop => Ok(()),
+            Instruction::Push(ref a) | Instruction::Pop(ref a) | Instruction::Jmp(ref a) |
+            Instruction::Call(ref a) | Instruction::Out(ref a) | Instruction::In(ref a) => {
+                write!(f, " {:?}", a)
+            },
+            Instruction::Set(ref a, ref b) | Instruction::Jt(ref a, ref b) | Instruction::Jf(ref a, ref b) |
+            Instruction::Not(ref a, ref b) | Instruction::RMem(ref a, ref b) | Instruction::WMem(ref a, ref b) => {
+                write!(f, " {:?} {:?}", a, b)
+            },
+            Instruction::Eq(ref a, ref b, ref c) | Instruction::Gt(ref a, ref b, ref c) |
+            Instruction::Add(ref a, ref b, ref c) | Instruction::Mult(ref a, ref b, ref c) |
+            Instruction::Mod(ref a, ref b, ref c) | Instruction::And(ref a, ref b, ref c) |
+            Instruction::Or(ref a, ref b, ref c) => {
+                write!(f, " {:?} {:?} {:?}", a, b, c)
+            },
+        }
+    }
+}
+
+impl TryFromPointer for Instruction {
+    fn try_from_pointer(ptr: &mut Pointer) -> Result<Instruction, InvalidInstruction> {
+        let n = match ptr.try_next() {
+            Some(n) => n,
+            None => return Err(InvalidInstruction(0)),
+        };
+        match n {
+            0 => Ok(Instruction::Halt),
+            1 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::Set(a, b)),
+            2 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::Push(a)),
+            3 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::Pop(a)),
+            4 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Eq(a, b, c)),
+            5 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Gt(a, b, c)),
+            6 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::Jmp(a)),
+            7 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::Jt(a, b)),
+            8 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::Jf(a, b)),
+            9 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Add(a, b, c)),
+            10 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Mult(a, b, c)),
+            11 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Mod(a, b, c)),
+            12 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::And(a, b, c)),
+            13 => TryFromPointer::try_from_pointer(ptr).map(|(a, b, c)| Instruction::Or(a, b, c)),
+            14 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::Not(a, b)),
+            15 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::RMem(a, b)),
+            16 => TryFromPointer::try_from_pointer(ptr).map(|(a, b)| Instruction::WMem(a, b)),
+            17 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::Call(a)),
+            18 => Ok(Instruction::Ret),
+            19 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::Out(a)),
+            20 => TryFromPointer::try_from_pointer(ptr).map(|a| Instruction::In(a)),
+            21 => Ok(Instruction::Noop),
+            _ => Err(InvalidInstruction(n)),
+        }
+    }
+}
+
+
+/// Outcome of `VM::run_with_limit`: either the program halted on its own, or
+/// the step budget ran out first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    Halted,
+    BudgetExhausted,
+}
+
+pub struct VM {
+    mem: Memory,
+    reg: [u16; NUM_REGISTERS],
+    stack: Vec<u16>,
+    ip: usize,
+    halted: bool,
+    cycles: u16,
+    total_cycles: u64,
+    input: Box<dyn InputSource>,
+    output: Box<dyn OutputSink>,
+}
+
+impl fmt::Debug for VM {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("VM")
+            .field("reg", &self.reg)
+            .field("stack", &self.stack)
+            .field("ip", &self.ip)
+            .field("halted", &self.halted)
+            .field("cycles", &self.cycles)
+            .field("total_cycles", &self.total_cycles)
+            .finish()
+    }
+}
+
+pub const NUM_REGISTERS: usize = 8;
+
+impl VM {
+    /// Creates a VM wired up to the process' standard input/output.
+    #[cfg(feature = "std")]
+    pub fn new(mem: Memory) -> VM {
+        VM::with_io(mem, Box::new(StdinInput), Box::new(StdoutOutput))
+    }
+
+    /// Creates a VM with the given input source and output sink, for
+    /// embedding in a host that doesn't go through stdio (or isn't `std` at
+    /// all).
+    pub fn with_io(mem: Memory, input: Box<dyn InputSource>, output: Box<dyn OutputSink>) -> VM {
+        VM {
+            mem: mem,
+            reg: [0; NUM_REGISTERS],
+            stack: Vec::new(),
+            ip: 0,
+            halted: false,
+            cycles: 0,
+            total_cycles: 0,
+            input: input,
+            output: output,
+        }
+    }
+
+    /// Address of the next instruction to execute, or of the instruction that
+    /// last trapped.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Reads the word at `*addr`, advancing it, via the non-panicking
+    /// `Memory::get` (unlike `Pointer`, which derefs through the panicking
+    /// `Index` impl). Out-of-bounds addresses become a recoverable trap
+    /// instead of aborting the process.
+    fn fetch(&self, addr: &mut usize) -> Result<u16, Trap> {
+        let n = self.mem.get(*addr).ok_or(Trap::MemoryOutOfBounds { addr: *addr })?;
+        *addr += 1;
+        Ok(n)
+    }
+
+    fn operand(&self, addr: &mut usize) -> Result<Operand, Trap> {
+        let n = self.fetch(addr)?;
+        Operand::try_from(n).map_err(|InvalidInstruction(n)| Trap::InvalidOperand(n))
+    }
+
+    fn next(&mut self) -> Result<Instruction, Trap> {
+        let mut addr = self.ip;
+        let n = self.fetch(&mut addr)?;
+        let ins = match n {
+            0 => Ok(Instruction::Halt),
+            1 => Ok(Instruction::Set(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            2 => Ok(Instruction::Push(self.operand(&mut addr)?)),
+            3 => Ok(Instruction::Pop(self.operand(&mut addr)?)),
+            4 => Ok(Instruction::Eq(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            5 => Ok(Instruction::Gt(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            6 => Ok(Instruction::Jmp(self.operand(&mut addr)?)),
+            7 => Ok(Instruction::Jt(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            8 => Ok(Instruction::Jf(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            9 => Ok(Instruction::Add(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            10 => Ok(Instruction::Mult(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            11 => Ok(Instruction::Mod(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            12 => Ok(Instruction::And(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            13 => Ok(Instruction::Or(self.operand(&mut addr)?, self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            14 => Ok(Instruction::Not(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            15 => Ok(Instruction::RMem(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            16 => Ok(Instruction::WMem(self.operand(&mut addr)?, self.operand(&mut addr)?)),
+            17 => Ok(Instruction::Call(self.operand(&mut addr)?)),
+            18 => Ok(Instruction::Ret),
+            19 => Ok(Instruction::Out(self.operand(&mut addr)?)),
+            20 => Ok(Instruction::In(self.operand(&mut addr)?)),
+            21 => Ok(Instruction::Noop),
+            _ => Err(Trap::InvalidInstruction(n)),
+        }?;
+        self.ip = addr;
+        Ok(ins)
+    }
+
+    pub fn step(&mut self) -> Result<(), Trap> {
+        if self.halted { return Ok(()) }
+        let addr = self.ip;
+        let instruction = self.next()?;
+        debug!("{:#06x} {:?}", addr, instruction);
+        self.cycles = self.cycles.wrapping_add(1);
+        self.total_cycles += 1;
+        instruction.execute(self).map_err(|trap| {
+            self.ip = addr;
+            trap
+        })
+    }
+
+    pub fn run(&mut self) -> Result<(), Trap> {
+        while !self.halted {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Like `run`, but stops after `max_steps` instructions have been
+    /// executed instead of running forever on a buggy or adversarial program.
+    pub fn run_with_limit(&mut self, max_steps: u64) -> Result<RunOutcome, Trap> {
+        let mut executed = 0;
+        while !self.halted {
+            if executed >= max_steps {
+                return Ok(RunOutcome::BudgetExhausted);
+            }
+            self.step()?;
+            executed += 1;
+        }
+        Ok(RunOutcome::Halted)
+    }
+
+    /// Instruction counter, wrapping at `u16::MAX` the way a real device
+    /// timer would. See `total_cycles` for a counter that doesn't wrap.
+    pub fn cycles(&self) -> u16 {
+        self.cycles
+    }
+
+    /// Total number of instructions executed since this VM was created.
+    pub fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{LAST_ADDRESS, Memory};
+
+    #[test]
+    fn operands() {
+        assert_eq!(Operand::from(0), Operand::Literal(0));
+        assert_eq!(Operand::from(32767), Operand::Literal(32767));
+        assert_eq!(Operand::from(32768), Operand::Register(0));
+        assert_eq!(Operand::from(32775), Operand::Register(7));
+    }
+
+    #[test]
+    fn operand_fetching() {
+        let mut mem = Memory::new();
+        mem[0] = 0x1234;
+        mem[1] = 0x5678;
+        mem[2] = 0x8005;
+        let mut ptr = mem.pointer(0);
+        assert_eq!(Operand::try_from_pointer(&mut ptr), Ok(Operand::Literal(0x1234)));
+        assert_eq!(Operand::try_from_pointer(&mut ptr), Ok(Operand::Literal(0x5678)));
+        assert_eq!(Operand::try_from_pointer(&mut ptr), Ok(Operand::Register(5)));
+    }
+
+    #[test]
+    fn instruction_fetching() {
+        let mut mem = Memory::new();
+        mem[0] = 9;
+        mem[1] = 32768;
+        mem[2] = 32769;
+        mem[3] = 4;
+        mem[4] = 19;
+        mem[5] = 32768;
+        let mut ptr = mem.pointer(0);
+        assert_eq!(Instruction::try_from_pointer(&mut ptr), Ok(Instruction::Add(Operand::Register(0), Operand::Register(1), Operand::Literal(4))));
+        assert_eq!(Instruction::try_from_pointer(&mut ptr), Ok(Instruction::Out(Operand::Register(0))));
+    }
+
+    #[test]
+    fn vm() {
+        let _ = VM::new(Memory::new());
+    }
+
+    struct NoInput;
+
+    impl InputSource for NoInput {
+        fn read_byte(&mut self) -> Option<u8> {
+            None
+        }
+    }
+
+    struct NullOutput;
+
+    impl OutputSink for NullOutput {
+        fn write_byte(&mut self, _byte: u8) {}
+    }
+
+    #[test]
+    fn trap_stack_underflow() {
+        let mut mem = Memory::new();
+        mem[0] = 3; // pop R0
+        mem[1] = MEMORY_SIZE as u16;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.step(), Err(Trap::StackUnderflow));
+    }
+
+    #[test]
+    fn trap_divide_by_zero() {
+        let mut mem = Memory::new();
+        mem[0] = 11; // mod R0, 5, 0
+        mem[1] = MEMORY_SIZE as u16;
+        mem[2] = 5;
+        mem[3] = 0;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.step(), Err(Trap::DivideByZero));
+    }
+
+    #[test]
+    fn trap_write_to_literal() {
+        let mut mem = Memory::new();
+        mem[0] = 1; // set 4, 5
+        mem[1] = 4;
+        mem[2] = 5;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.step(), Err(Trap::WriteToLiteral));
+    }
+
+    #[test]
+    fn trap_invalid_instruction() {
+        let mut mem = Memory::new();
+        mem[0] = 99;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.step(), Err(Trap::InvalidInstruction(99)));
+    }
+
+    #[test]
+    fn trap_invalid_operand() {
+        let mut mem = Memory::new();
+        mem[0] = 19; // out 0xffff
+        mem[1] = 0xffff;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.step(), Err(Trap::InvalidOperand(0xffff)));
+    }
+
+    #[test]
+    fn trap_input_closed() {
+        let mut mem = Memory::new();
+        mem[0] = 20; // in R0
+        mem[1] = MEMORY_SIZE as u16;
+        let mut vm = VM::with_io(mem, Box::new(NoInput), Box::new(NullOutput));
+        assert_eq!(vm.step(), Err(Trap::InputClosed));
+    }
+
+    #[test]
+    fn trap_memory_out_of_bounds_on_fetch() {
+        let mut mem = Memory::new();
+        mem[0] = 6; // jmp 0x7fff
+        mem[1] = LAST_ADDRESS as u16;
+        mem[LAST_ADDRESS] = 2; // push <operand past the end of memory>
+        let mut vm = VM::new(mem);
+        vm.step().unwrap();
+        assert_eq!(vm.step(), Err(Trap::MemoryOutOfBounds { addr: MEMORY_SIZE }));
+    }
+
+    #[test]
+    fn run_with_limit_halts_within_budget() {
+        let mut mem = Memory::new();
+        mem[0] = 0; // halt
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.run_with_limit(10), Ok(RunOutcome::Halted));
+        assert_eq!(vm.total_cycles(), 1);
+    }
+
+    #[test]
+    fn run_with_limit_exhausts_budget() {
+        let mut mem = Memory::new();
+        mem[0] = 6; // jmp 0 (infinite loop)
+        mem[1] = 0;
+        let mut vm = VM::new(mem);
+        assert_eq!(vm.run_with_limit(5), Ok(RunOutcome::BudgetExhausted));
+        assert_eq!(vm.total_cycles(), 5);
+    }
+
+    #[test]
+    fn cycles_wraps_while_total_cycles_does_not() {
+        let mut mem = Memory::new();
+        mem[0] = 6; // jmp 0 (infinite loop)
+        mem[1] = 0;
+        let mut vm = VM::new(mem);
+        let steps = u16::max_value() as u64 + 5;
+        assert_eq!(vm.run_with_limit(steps), Ok(RunOutcome::BudgetExhausted));
+        assert_eq!(vm.total_cycles(), steps);
+        assert_eq!(vm.cycles(), 4);
+    }
+}